@@ -1,34 +1,283 @@
 //! Contains the [`ChangeNotifier`] type that can be used to listen to changes to variable
 
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+
+use futures_lite::Stream;
 
 use crate::event::{Event, EventListener};
 
 /// A wrapper type for subscribing to changes to the inner type
-#[derive(Debug)]
 pub struct ChangeNotifier<T> {
     inner: T,
     event: Arc<Event>,
+    version: Arc<AtomicU64>,
+    /// How many [`ChangeListener`]s handed out by [`listen`](ChangeNotifier::listen) (and the
+    /// `wait_*`/`closed` helpers built on it) are currently parked. Tracked locally instead of
+    /// trusting a `total_listeners`-style method to exist on the `event` fork.
+    parked: Arc<AtomicUsize>,
+    /// Per-subscriber `(old, new)` buffers registered by [`get_changes`](ChangeNotifier::get_changes),
+    /// held weakly so a dropped [`ChangeStream`] is pruned on the next push instead of leaking.
+    change_buffers: Arc<Mutex<Vec<Weak<Mutex<VecDeque<(T, T)>>>>>>,
+    /// How many [`ChangeNotifier`] clones sharing this state are still alive, including `self`.
+    /// [`Clone::clone`] increments it and [`Drop::drop`] decrements it explicitly (rather than
+    /// relying on [`Arc::strong_count`] of a shared token), so the count a waiter observes after
+    /// being woken by a `drop` is always the post-decrement one — see the [`Drop`] impl.
+    owners: Arc<AtomicUsize>,
+}
+
+/// The outcome of [`ChangeNotifier::wait_or_closed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitOutcome {
+    /// The value changed.
+    Changed,
+    /// Every other [`ChangeNotifier`] sharing this state has been dropped; there will be no more
+    /// changes to wait for.
+    Closed,
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for ChangeNotifier<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeNotifier")
+            .field("inner", &self.inner)
+            .field("listener_count", &self.listener_count())
+            .finish()
+    }
 }
 
 impl<T: Clone> Clone for ChangeNotifier<T> {
     fn clone(&self) -> Self {
+        self.owners.fetch_add(1, Ordering::SeqCst);
         ChangeNotifier {
             inner: self.inner.clone(),
             event: self.event.clone(),
+            version: self.version.clone(),
+            parked: self.parked.clone(),
+            change_buffers: self.change_buffers.clone(),
+            owners: self.owners.clone(),
         }
     }
 }
 
+impl<T> Drop for ChangeNotifier<T> {
+    fn drop(&mut self) {
+        // Decrement *before* notifying: `self.owners` is only dropped (as a field) after this
+        // function returns, so if we notified first, a waiter woken by this exact call could
+        // still read the pre-decrement count and park right back up — a lost wakeup on close.
+        // Decrementing first guarantees the count `notify_all` wakes everyone up to observe is
+        // already the post-drop one.
+        self.owners.fetch_sub(1, Ordering::SeqCst);
+        self.event.notify_all();
+    }
+}
+
 impl<T> ChangeNotifier<T> {
     /// Create a new [`ChangeNotifier`] wrapping the given data.
     pub fn new(data: T) -> Self {
         ChangeNotifier {
             inner: data,
             event: Arc::new(Event::new()),
+            version: Arc::new(AtomicU64::new(0)),
+            parked: Arc::new(AtomicUsize::new(0)),
+            change_buffers: Arc::new(Mutex::new(Vec::new())),
+            owners: Arc::new(AtomicUsize::new(1)),
+        }
+    }
+
+    /// Whether this is the only remaining clone of this [`ChangeNotifier`] — i.e. every other
+    /// handle that could still call [`update`](Self::update) has been dropped.
+    pub fn is_closed(&self) -> bool {
+        // `owners` counts every live clone including `self`, so "every other clone is gone"
+        // means exactly one owner (this one) remains.
+        self.owners.load(Ordering::SeqCst) == 1
+    }
+
+    /// Resolve once every other [`ChangeNotifier`] clone sharing this state has been dropped.
+    ///
+    /// Returns immediately if it's already closed.
+    pub async fn closed(&self) {
+        loop {
+            if self.is_closed() {
+                return;
+            }
+
+            let listener = self.listen();
+
+            if self.is_closed() {
+                return;
+            }
+
+            listener.await;
+        }
+    }
+
+    /// Wait for either the next change or for this notifier to be closed, whichever comes first.
+    ///
+    /// This mirrors how a producer can observe all its receivers dropping: it lets a consumer
+    /// exit its wait loop cleanly on [`WaitOutcome::Closed`] instead of leaking a task parked
+    /// forever on [`listen`](Self::listen).
+    pub async fn wait_or_closed(&self) -> WaitOutcome {
+        let seen = self.version();
+
+        loop {
+            if self.is_closed() {
+                return WaitOutcome::Closed;
+            }
+
+            let listener = self.listen();
+
+            if self.is_closed() {
+                return WaitOutcome::Closed;
+            }
+
+            // Dropping a clone that isn't the last one also wakes listeners, so only report
+            // `Changed` once the version has actually moved.
+            if self.version() > seen {
+                return WaitOutcome::Changed;
+            }
+
+            listener.await;
+        }
+    }
+
+    /// The current version of the inner data.
+    ///
+    /// This starts at `0` and is incremented by one on every [`update`](Self::update) call, so a
+    /// listener that's been handed a version number can tell whether it's fallen behind, and by
+    /// how much, instead of only knowing that *a* change happened.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the version has advanced past `seen`, then return the new version.
+    ///
+    /// If the version has already moved past `seen` by the time this is called, it returns
+    /// immediately. Otherwise it registers a listener, re-checks the version to close the race
+    /// where an [`update`](Self::update) landed in between, and only then awaits — so a consumer
+    /// that's processed up through version `seen` can ask to be woken by exactly the next change
+    /// without risking a lost wakeup.
+    pub async fn wait_for_change_since(&self, seen: u64) -> u64 {
+        loop {
+            let current = self.version();
+            if current > seen {
+                return current;
+            }
+
+            let listener = self.listen();
+
+            let current = self.version();
+            if current > seen {
+                return current;
+            }
+
+            listener.await;
+        }
+    }
+
+    /// Get a listener for changes to the inner data.
+    pub fn listen(&self) -> ChangeListener {
+        listen_on(&self.event, &self.parked)
+    }
+
+    /// The number of listeners currently actually parked waiting on this notifier — i.e. that
+    /// have polled (or blocked) at least once and are genuinely waiting on a notification, not
+    /// every [`ChangeListener`] that's merely been constructed.
+    ///
+    /// Handy when tuning backpressure: a growing listener count means consumers aren't keeping
+    /// up with however fast this value is being updated. Tracked locally by [`listen`](Self::listen)
+    /// and [`ChangeListener`] rather than through the `event` fork's own API, whose surface this
+    /// crate can't rely on being stable.
+    pub fn listener_count(&self) -> usize {
+        self.parked.load(Ordering::SeqCst)
+    }
+
+    /// Asynchronously wait until the inner data satisfies `pred`.
+    ///
+    /// This is the condvar-style wait loop done correctly: it checks `pred` once before
+    /// registering a listener and once again right after, so an [`update`](Self::update) that
+    /// lands between the two checks can't be missed, the way a hand-rolled `listen().await` loop
+    /// is prone to missing it.
+    ///
+    /// Unlike a real [`Condvar`](std::sync::Condvar), there's no lock guarding `T`: `pred` is run
+    /// against whichever instance `self` is. If the update that's supposed to satisfy `pred` is
+    /// applied through a *different* clone of this notifier, it changes that clone's own `inner`,
+    /// not this one's, and this call will wait forever. Only the instance being updated (or a `T`
+    /// that's itself shared and interior-mutable, e.g. `Arc<AtomicBool>`) will ever be observed here.
+    pub async fn wait_until<F: Fn(&T) -> bool>(&self, pred: F) {
+        self.wait_until_mapped(|data| if pred(data) { Some(()) } else { None })
+            .await
+    }
+
+    /// Blocking variant of [`wait_until`](Self::wait_until), for callers outside an async
+    /// context.
+    pub fn wait_until_blocking<F: Fn(&T) -> bool>(&self, pred: F) {
+        self.wait_until_mapped_blocking(|data| if pred(data) { Some(()) } else { None })
+    }
+
+    /// Asynchronously wait until `pred` returns `Some`, then return the extracted value —
+    /// mirroring how a [`Condvar`](std::sync::Condvar) hands back the guarded state once its
+    /// predicate is satisfied, instead of just a bare notification.
+    ///
+    /// See [`wait_until`](Self::wait_until) for the same-instance/shared-`T` caveat: this only
+    /// ever sees updates applied to `self`, not to some other clone of this notifier.
+    pub async fn wait_until_mapped<F, R>(&self, pred: F) -> R
+    where
+        F: Fn(&T) -> Option<R>,
+    {
+        loop {
+            if let Some(value) = pred(&self.inner) {
+                return value;
+            }
+
+            let listener = self.listen();
+
+            // Re-check now that we're registered: an update may have landed between the first
+            // check above and this one, and we must not await a notification we already missed.
+            if let Some(value) = pred(&self.inner) {
+                return value;
+            }
+
+            listener.await;
+        }
+    }
+
+    /// Blocking variant of [`wait_until_mapped`](Self::wait_until_mapped).
+    pub fn wait_until_mapped_blocking<F, R>(&self, pred: F) -> R
+    where
+        F: Fn(&T) -> Option<R>,
+    {
+        loop {
+            if let Some(value) = pred(&self.inner) {
+                return value;
+            }
+
+            let listener = self.listen();
+
+            if let Some(value) = pred(&self.inner) {
+                return value;
+            }
+
+            listener.wait();
         }
     }
 
+    /// Bump the version and wake every parked listener.
+    ///
+    /// Shared by [`update`](Self::update) and [`update_if_changed`](Self::update_if_changed),
+    /// which differ only in *whether* they decide to call this, not in what it does.
+    fn notify_change(&self) {
+        // Bump the version before notifying so that any listener woken by this call observes
+        // a version number that already reflects this update.
+        self.version.fetch_add(1, Ordering::SeqCst);
+        self.event.notify_all();
+    }
+}
+
+impl<T> ChangeNotifier<T> {
     /// Update the inner data by passing a closure to do the mutation
     ///
     /// > **Note:** Listeners will *only* be notified to changes of the inner data
@@ -41,16 +290,168 @@ impl<T> ChangeNotifier<T> {
         // Apply the update to the inner type
         let ret = apply_update(&mut self.inner);
 
-        // Notify all listeners of the change
-        self.event.notify_all();
+        self.notify_change();
 
         // Return the return value of the apply update function
         ret
     }
+}
+
+impl<T: Clone + PartialEq> ChangeNotifier<T> {
+    /// Update the inner data like [`update`](Self::update), but only notify listeners if the
+    /// value actually changed.
+    ///
+    /// `update` always notifies, even when the closure leaves the value identical, which means a
+    /// hot update path pays for spurious wakeups on every call. Use this instead wherever callers
+    /// should opt into change-coalescing semantics — comparing old and new with `PartialEq`
+    /// before deciding whether to notify.
+    pub fn update_if_changed<R, F>(&mut self, apply_update: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        let old = self.inner.clone();
+
+        let ret = apply_update(&mut self.inner);
+
+        if self.inner != old {
+            let new = self.inner.clone();
+            self.push_change(old, new);
+            self.notify_change();
+        }
+
+        ret
+    }
+
+    /// Push `(old, new)` into every subscriber buffer registered by
+    /// [`get_changes`](Self::get_changes), pruning any whose [`ChangeStream`] has since been
+    /// dropped.
+    fn push_change(&self, old: T, new: T) {
+        let mut buffers = self.change_buffers.lock().unwrap();
+        buffers.retain(|buffer| match buffer.upgrade() {
+            Some(buffer) => {
+                buffer.lock().unwrap().push_back((old.clone(), new.clone()));
+                true
+            }
+            None => false,
+        });
+    }
+
+    /// Get a [`Stream`] of `(old_value, new_value)` pairs describing how the inner data changes
+    /// from the moment this is called onward.
+    ///
+    /// Unlike [`listen`](Self::listen), which only tells a subscriber that *something* changed,
+    /// this hands reactive consumers the actual values involved instead of forcing them to race
+    /// to re-read `self`. Each call registers its own buffer, fed by [`push_change`](Self::push_change)
+    /// inside [`update_if_changed`](Self::update_if_changed) — plain [`update`](Self::update) can't
+    /// feed it, since producing a real `(old, new)` pair requires cloning the value on both sides
+    /// of the mutation, which would force a `Clone` bound onto every caller of `update`, including
+    /// ones using non-`Clone` types like `AtomicBool`.
+    pub fn get_changes(&self) -> impl Stream<Item = (T, T)> {
+        let buffer = Arc::new(Mutex::new(VecDeque::new()));
+        self.change_buffers
+            .lock()
+            .unwrap()
+            .push(Arc::downgrade(&buffer));
+
+        ChangeStream {
+            event: self.event.clone(),
+            parked: self.parked.clone(),
+            listener: self.listen(),
+            buffer,
+        }
+    }
+}
+
+/// The [`Stream`] of value changes returned by [`ChangeNotifier::get_changes`]
+///
+/// This deliberately holds only the `event`/`parked` Arcs it needs to re-register a listener,
+/// not a whole [`ChangeNotifier`] clone — so a live stream is never counted as one of the
+/// notifier's `owners` and can't keep it from ever closing.
+struct ChangeStream<T> {
+    event: Arc<Event>,
+    parked: Arc<AtomicUsize>,
+    listener: ChangeListener,
+    buffer: Arc<Mutex<VecDeque<(T, T)>>>,
+}
+
+impl<T> Stream for ChangeStream<T> {
+    type Item = (T, T);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(pair) = this.buffer.lock().unwrap().pop_front() {
+                return Poll::Ready(Some(pair));
+            }
+
+            match Pin::new(&mut this.listener).poll(cx) {
+                // The listener only fires once; re-register so we notice the next change, then
+                // loop back around to drain whatever `update_if_changed` just queued up.
+                Poll::Ready(()) => this.listener = listen_on(&this.event, &this.parked),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
 
-    /// Get an event listener for changes to the inner data
-    pub fn listen(&self) -> EventListener {
-        self.event.listen()
+/// Build a [`ChangeListener`] on the given `event`/`parked` pair, shared by
+/// [`ChangeNotifier::listen`] and [`ChangeStream`]'s re-registration so both go through the same
+/// not-yet-parked construction.
+fn listen_on(event: &Arc<Event>, parked: &Arc<AtomicUsize>) -> ChangeListener {
+    ChangeListener {
+        inner: event.listen(),
+        parked: parked.clone(),
+        registered: false,
+    }
+}
+
+/// A listener for changes to a [`ChangeNotifier`], returned by [`ChangeNotifier::listen`].
+///
+/// This wraps the underlying [`EventListener`] so that [`ChangeNotifier::listener_count`] can be
+/// tracked locally, instead of depending on the `event` fork exposing a `total_listeners`-style
+/// method of its own. It only counts itself in `parked` once it's actually been polled to
+/// [`Poll::Pending`] (or blocked via [`wait`](Self::wait)) — a listener that's constructed and
+/// dropped without ever truly waiting, as happens in the early-return double-check pattern used
+/// by [`wait_until`](ChangeNotifier::wait_until) and friends, never touches the count at all.
+pub struct ChangeListener {
+    inner: EventListener,
+    parked: Arc<AtomicUsize>,
+    registered: bool,
+}
+
+impl ChangeListener {
+    /// Block the current thread until notified.
+    pub fn wait(mut self) {
+        self.parked.fetch_add(1, Ordering::SeqCst);
+        self.registered = true;
+        self.inner.wait();
+    }
+}
+
+impl Future for ChangeListener {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(()),
+            Poll::Pending => {
+                if !this.registered {
+                    this.parked.fetch_add(1, Ordering::SeqCst);
+                    this.registered = true;
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for ChangeListener {
+    fn drop(&mut self) {
+        if self.registered {
+            self.parked.fetch_sub(1, Ordering::SeqCst);
+        }
     }
 }
 
@@ -62,6 +463,106 @@ impl<T> std::ops::Deref for ChangeNotifier<T> {
         &self.inner
     }
 }
+
+/// A guard returned by [`ChangeNotifier::watch_file`] that keeps the background file watcher
+/// alive. Dropping it stops the watcher thread.
+#[cfg(feature = "file-watch")]
+pub struct FileWatchGuard {
+    shutdown: Arc<std::sync::atomic::AtomicBool>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(feature = "file-watch")]
+impl Drop for FileWatchGuard {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(feature = "file-watch")]
+impl<T> ChangeNotifier<T>
+where
+    T: Clone + Send + 'static,
+{
+    /// Bind a [`ChangeNotifier`] to a file on disk.
+    ///
+    /// Every time the file is modified, created or removed, it's re-read, run through `parse`,
+    /// and applied through [`update`](Self::update), waking every listener — a drop-in hot-reload
+    /// primitive for config/state files so downstream crates don't have to reimplement the
+    /// watcher plumbing themselves. If the file can't be read when an event arrives (for example
+    /// because it was just removed), that event is skipped and no listener is woken.
+    ///
+    /// Returns the notifier together with a [`FileWatchGuard`]; dropping the guard stops the
+    /// background watcher thread.
+    ///
+    /// The notifier is wrapped in `Arc<Mutex<_>>` rather than handed back as a bare clone: a plain
+    /// [`ChangeNotifier`] clone has its own independent `inner`, so a watcher thread mutating one
+    /// clone would never be visible through another — the caller and the watcher thread need to be
+    /// operating on the exact same instance for hot-reload to actually do anything.
+    pub fn watch_file<P, F>(
+        path: P,
+        parse: F,
+    ) -> std::io::Result<(Arc<Mutex<Self>>, FileWatchGuard)>
+    where
+        P: AsRef<std::path::Path>,
+        F: Fn(&[u8]) -> T + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        let initial = parse(&std::fs::read(&path)?);
+        let notifier = Arc::new(Mutex::new(ChangeNotifier::new(initial)));
+        let watched = notifier.clone();
+
+        let shutdown = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let shutdown_ = shutdown.clone();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        notify::Watcher::watch(&mut watcher, &path, notify::RecursiveMode::NonRecursive)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+
+        let handle = std::thread::spawn(move || {
+            // Keep the watcher alive for as long as this thread runs.
+            let _watcher = watcher;
+
+            while !shutdown_.load(Ordering::SeqCst) {
+                let event = match rx.recv_timeout(std::time::Duration::from_millis(200)) {
+                    Ok(Ok(event)) => event,
+                    Ok(Err(_)) | Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+                };
+
+                let is_relevant = matches!(
+                    event.kind,
+                    notify::EventKind::Modify(notify::event::ModifyKind::Data(_))
+                        | notify::EventKind::Create(_)
+                        | notify::EventKind::Remove(_)
+                );
+
+                if is_relevant {
+                    if let Ok(bytes) = std::fs::read(&path) {
+                        let value = parse(&bytes);
+                        watched.lock().unwrap().update(|inner| *inner = value);
+                    }
+                }
+            }
+        });
+
+        Ok((
+            notifier,
+            FileWatchGuard {
+                shutdown,
+                handle: Some(handle),
+            },
+        ))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;